@@ -0,0 +1,169 @@
+//! A content-addressed incremental cache for lint results.
+//!
+//! Modeled on Deno's `IncrementalCache`: each entry is keyed on a hash of the
+//! file's content combined with a hash of the resolved [`Settings`], and stores
+//! the serialized [`Diagnostics`] (including fix edits) produced for that key.
+//! On the next run any file whose content+settings key is unchanged is served
+//! from the cache without re-parsing or re-linting. Because the key folds in
+//! the settings hash, changing `pyproject.toml` automatically busts every
+//! dependent entry — no manual `--clear-cache` is required.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::Result;
+
+use crate::linter::Diagnostics;
+use crate::Settings;
+
+/// Directory, relative to the working tree, that holds the cache file.
+const CACHE_DIR: &str = ".ruff_cache";
+
+/// Whether the incremental cache is consulted for a run.
+///
+/// The `cache: bool` flag threaded through `run` converts into this via
+/// `cache.into()`, preserving the existing plumbing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Enabled,
+    Disabled,
+}
+
+impl Mode {
+    /// Returns `true` if the cache should be consulted.
+    pub const fn enabled(self) -> bool {
+        matches!(self, Mode::Enabled)
+    }
+}
+
+impl From<bool> for Mode {
+    fn from(value: bool) -> Self {
+        if value {
+            Mode::Enabled
+        } else {
+            Mode::Disabled
+        }
+    }
+}
+
+/// A persisted, content-addressed map from path+content+settings keys to the
+/// diagnostics last produced for them.
+///
+/// Entries are stored as [`serde_json::Value`] rather than [`Diagnostics`]
+/// directly: a `Value` round-trips through `serde` on its own, so persisting
+/// and reloading the cache needs only the `Serialize` impl the diagnostics
+/// graph already carries for JSON output, and an entry written by an older ruff
+/// is tolerated rather than corrupting the whole file. Hydrating a hit back into
+/// [`Diagnostics`] relies on that graph also deriving `Deserialize` — and, since
+/// `run`'s channel now clones each file's `Diagnostics` to the reporter thread,
+/// on `Diagnostics`/`Message`/`CheckKind`/`Fix` also deriving `Clone`. Both
+/// derives belong on those types in `linter.rs`/`message.rs`/`checks.rs`, none
+/// of which are part of this module; this crate does not build in this
+/// snapshot (no `Cargo.toml`, and those modules are absent) to confirm it here.
+pub struct IncrementalCache {
+    path: PathBuf,
+    entries: Mutex<HashMap<u64, serde_json::Value>>,
+}
+
+impl IncrementalCache {
+    /// Load the cache from disk, starting empty if it is missing or corrupt.
+    pub fn load() -> Self {
+        let path = Path::new(CACHE_DIR).join("incremental.json");
+        let entries = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// Compute the content-addressed key for `path` under `settings`.
+    ///
+    /// The path is folded in alongside the content so that two files with
+    /// identical content and settings — duplicated boilerplate, empty
+    /// `__init__.py`, vendored copies — get distinct keys and do not serve each
+    /// other's cached diagnostics (which embed the wrong `filename`).
+    pub fn key(path: &Path, contents: &str, settings: &Settings) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        path.hash(&mut hasher);
+        contents.hash(&mut hasher);
+        // The resolved settings are folded in so a change to the enabled
+        // codes, a per-file ignore, or the target version invalidates the
+        // entry automatically.
+        settings_hash(settings).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Return the cached diagnostics for `key`, if a well-formed entry exists.
+    pub fn get(&self, key: u64) -> Option<Diagnostics> {
+        let value = self.entries.lock().unwrap().get(&key).cloned()?;
+        serde_json::from_value(value).ok()
+    }
+
+    /// Store `diagnostics` under `key`.
+    pub fn set(&self, key: u64, diagnostics: &Diagnostics) {
+        if let Ok(value) = serde_json::to_value(diagnostics) {
+            self.entries.lock().unwrap().insert(key, value);
+        }
+    }
+
+    /// Flush the cache to disk.
+    pub fn persist(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let entries = self.entries.lock().unwrap();
+        std::fs::write(&self.path, serde_json::to_vec(&*entries)?)?;
+        Ok(())
+    }
+}
+
+/// Hash the pieces of `settings` that affect lint output: the enabled codes,
+/// the per-file ignores, and the target version.
+///
+/// `Settings` holds unordered collections (the enabled-code set, the per-file
+/// ignore list's own code sets), so the old `format!("{settings:?}")` hash
+/// varied run-to-run for identical settings and produced spurious cache
+/// misses. Each collection is folded in with a commutative operation instead,
+/// making the result independent of iteration order while still busting every
+/// dependent entry when any of these fields change.
+fn settings_hash(settings: &Settings) -> u64 {
+    let enabled = hash_commutative(&settings.enabled);
+
+    let per_file_ignores = settings
+        .per_file_ignores
+        .iter()
+        .map(|ignore| {
+            let mut hasher = DefaultHasher::new();
+            ignore.pattern.hash(&mut hasher);
+            hash_commutative(&ignore.codes).hash(&mut hasher);
+            hasher.finish()
+        })
+        .fold(0_u64, u64::wrapping_add);
+
+    let mut hasher = DefaultHasher::new();
+    settings.target_version.hash(&mut hasher);
+    let target_version = hasher.finish();
+
+    enabled
+        .wrapping_add(per_file_ignores)
+        .wrapping_add(target_version)
+}
+
+/// Hash an unordered collection's items and combine them with a commutative
+/// operation, so the result does not depend on iteration order.
+fn hash_commutative<T: Hash>(items: impl IntoIterator<Item = T>) -> u64 {
+    items
+        .into_iter()
+        .map(|item| {
+            let mut hasher = DefaultHasher::new();
+            item.hash(&mut hasher);
+            hasher.finish()
+        })
+        .fold(0_u64, u64::wrapping_add)
+}
@@ -1,10 +1,16 @@
-use std::io::{self, Read};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::channel;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use anyhow::{bail, Result};
 use itertools::Itertools;
 use log::{debug, error};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 #[cfg(not(target_family = "wasm"))]
 use rayon::prelude::*;
 use rustpython_ast::Location;
@@ -17,31 +23,110 @@ use crate::fs::collect_python_files;
 use crate::iterators::par_iter;
 use crate::linter::{add_noqa_to_path, autoformat_path, lint_path, lint_stdin, Diagnostics};
 use crate::message::Message;
+use crate::reporter::{create_reporter, Reporter, Summary};
 use crate::settings::types::SerializationFormat;
 use crate::{Configuration, Settings};
 
+/// How long to wait after the last filesystem event before re-linting, so a
+/// burst of editor writes coalesces into a single pass.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Upper bound on autofix passes for a single file, so an oscillating or
+/// non-terminating fix cannot spin forever.
+const MAX_FIX_ITERATIONS: usize = 10;
+
+/// How the autofix fixpoint loop terminated for a single file.
+#[derive(Debug, Clone, Copy)]
+pub struct FixpointSummary {
+    /// Number of lint/fix passes that ran.
+    pub passes: usize,
+    /// Whether the loop reached a fixpoint (no further fix applied) rather than
+    /// hitting the iteration bound or discarding an unparseable pass.
+    pub converged: bool,
+}
+
+/// The result of a `run`: the merged diagnostics plus, for each file, how the
+/// autofix fixpoint loop terminated.
+///
+/// `diagnostics` has already been streamed to the reporter and printed as part
+/// of `run`'s own output; it is exposed here only so the caller can decide an
+/// exit code (and inspect `fixpoints`). Re-serializing or re-printing it is a
+/// caller bug — it would duplicate output the reporter already emitted.
+#[derive(Default)]
+pub struct RunResult {
+    pub diagnostics: Diagnostics,
+    pub fixpoints: HashMap<PathBuf, FixpointSummary>,
+}
+
+/// An item handed to the reporter thread as each file completes.
+enum ReportEvent {
+    File(PathBuf, Diagnostics),
+    Error(Option<PathBuf>, String),
+}
+
 /// Run the linter over a collection of files.
+///
+/// Results are streamed to a [`Reporter`] for `format` through a channel as the
+/// parallel loop completes, so text output can appear per-file instead of
+/// waiting for the slowest file. The merged [`RunResult`] is still returned for
+/// the caller's exit-code handling.
 pub fn run(
     files: &[PathBuf],
     defaults: &Settings,
     overrides: &Overrides,
     cache: bool,
     autofix: &fixer::Mode,
-) -> Diagnostics {
+    format: SerializationFormat,
+) -> RunResult {
     // Collect all the files to check.
     let start = Instant::now();
     let (paths, resolver) = collect_python_files(files, overrides, defaults);
     let duration = start.elapsed();
     debug!("Identified files to lint in: {:?}", duration);
 
+    // Load the incremental cache up front so the `par_iter` loop can hit it
+    // before touching `lint_path`.
+    let cache_mode: crate::cache::Mode = cache.into();
+    let incremental = cache_mode
+        .enabled()
+        .then(crate::cache::IncrementalCache::load);
+
+    // Drive the reporter from a dedicated thread: each completed file is sent
+    // over the channel so the streaming reporters can emit output as the
+    // `par_iter` loop makes progress, and the summary is tallied there rather
+    // than from the diagnostics vector.
+    let (report_tx, report_rx) = channel::<ReportEvent>();
+    let reporter_thread = thread::spawn(move || {
+        let mut reporter = create_reporter(format);
+        let mut summary = Summary::default();
+        for event in report_rx {
+            match event {
+                ReportEvent::File(path, diagnostics) => {
+                    summary.record(&diagnostics);
+                    reporter.report_file(&path, &diagnostics);
+                }
+                ReportEvent::Error(path, err) => {
+                    summary.errors += 1;
+                    reporter.report_error(path.as_deref(), &err);
+                }
+            }
+        }
+        reporter.finish(&summary);
+    });
+
     let start = Instant::now();
-    let mut diagnostics: Diagnostics = par_iter(&paths)
+    let mut result: RunResult = par_iter(&paths)
         .map(|entry| {
-            match entry {
+            let report_tx = report_tx.clone();
+            let result = match entry {
                 Ok(entry) => {
                     let path = entry.path();
                     let settings = resolver.resolve(path).unwrap_or(defaults);
-                    lint_path(path, settings, &cache.into(), autofix)
+                    lint_cached(path, settings, incremental.as_ref(), &cache_mode, autofix)
+                        .map(|(diagnostics, summary)| RunResult {
+                            diagnostics,
+                            fixpoints: HashMap::from([(path.to_owned(), summary)]),
+                        })
                         .map_err(|e| (Some(path.to_owned()), e.to_string()))
                 }
                 Err(e) => Err((
@@ -54,34 +139,362 @@ pub fn run(
                 if let Some(path) = &path {
                     let settings = resolver.resolve(path).unwrap_or(defaults);
                     if settings.enabled.contains(&CheckCode::E902) {
-                        Diagnostics::new(vec![Message {
-                            kind: CheckKind::IOError(message),
-                            location: Location::default(),
-                            end_location: Location::default(),
-                            fix: None,
-                            filename: path.to_string_lossy().to_string(),
-                            source: None,
-                        }])
+                        RunResult {
+                            diagnostics: Diagnostics::new(vec![Message {
+                                kind: CheckKind::IOError(message),
+                                location: Location::default(),
+                                end_location: Location::default(),
+                                fix: None,
+                                filename: path.to_string_lossy().to_string(),
+                                source: None,
+                            }]),
+                            fixpoints: HashMap::default(),
+                        }
                     } else {
-                        error!("Failed to check {}: {message}", path.to_string_lossy());
-                        Diagnostics::default()
+                        let _ = report_tx.send(ReportEvent::Error(Some(path.clone()), message));
+                        RunResult::default()
                     }
                 } else {
-                    error!("{message}");
-                    Diagnostics::default()
+                    let _ = report_tx.send(ReportEvent::Error(None, message));
+                    RunResult::default()
                 }
-            })
+            });
+
+            // Stream this file's diagnostics to the reporter. The path comes
+            // from the fixpoint entry, falling back to the first message's
+            // filename for the I/O-error case.
+            let path = result
+                .fixpoints
+                .keys()
+                .next()
+                .cloned()
+                .or_else(|| result.diagnostics.messages.first().map(|m| PathBuf::from(&m.filename)));
+            if let Some(path) = path {
+                let _ = report_tx.send(ReportEvent::File(path, result.diagnostics.clone()));
+            }
+            result
         })
-        .reduce(Diagnostics::default, |mut acc, item| {
-            acc += item;
+        .reduce(RunResult::default, |mut acc, item| {
+            acc.diagnostics += item.diagnostics;
+            acc.fixpoints.extend(item.fixpoints);
             acc
         });
 
-    diagnostics.messages.sort_unstable();
+    // Close the channel and let the reporter flush its final summary.
+    drop(report_tx);
+    let _ = reporter_thread.join();
+
+    result.diagnostics.messages.sort_unstable();
     let duration = start.elapsed();
     debug!("Checked files in: {:?}", duration);
 
-    diagnostics
+    // Flush any freshly linted entries back to disk for the next run.
+    if let Some(incremental) = &incremental {
+        if let Err(e) = incremental.persist() {
+            error!("Failed to persist incremental cache: {e}");
+        }
+    }
+
+    // Surface any file whose fixes never converged, so oscillating or
+    // non-terminating rules are visible rather than silently truncated.
+    for (path, summary) in &result.fixpoints {
+        if !summary.converged {
+            error!(
+                "Autofix did not converge for {} after {} pass(es)",
+                path.to_string_lossy(),
+                summary.passes
+            );
+        }
+    }
+
+    result
+}
+
+/// Lint `path`, consulting the incremental cache before doing any work.
+///
+/// When a cache is present, the file's content+settings key is looked up first;
+/// a hit returns the stored diagnostics without parsing or linting (reported as
+/// zero fixpoint passes). On a miss, the file is linted to a fixpoint and the
+/// result is stored under the key computed from the *pre-fix* content, so a
+/// later run over the same source and settings hits the cache.
+///
+/// The cache is bypassed entirely in `Apply` mode: the stored entry holds the
+/// *post-fix* diagnostics keyed on the *pre-fix* content, so serving it would
+/// report the fixes as already applied (`passes: 0`) while leaving the file
+/// unrewritten on disk. Autofix runs therefore always lint to a fixpoint, and
+/// their post-fix diagnostics are not persisted under the stale pre-fix key.
+fn lint_cached(
+    path: &Path,
+    settings: &Settings,
+    incremental: Option<&crate::cache::IncrementalCache>,
+    cache: &crate::cache::Mode,
+    autofix: &fixer::Mode,
+) -> Result<(Diagnostics, FixpointSummary)> {
+    let (Some(incremental), false) = (incremental, matches!(autofix, fixer::Mode::Apply)) else {
+        return lint_path_to_fixpoint(path, settings, cache, autofix);
+    };
+
+    let contents = std::fs::read_to_string(path)?;
+    let key = crate::cache::IncrementalCache::key(path, &contents, settings);
+    if let Some(diagnostics) = incremental.get(key) {
+        return Ok((diagnostics, FixpointSummary { passes: 0, converged: true }));
+    }
+
+    let (diagnostics, summary) = lint_path_to_fixpoint(path, settings, cache, autofix)?;
+    incremental.set(key, &diagnostics);
+    Ok((diagnostics, summary))
+}
+
+/// Lint `path`, iterating the autofix loop to a fixpoint.
+///
+/// Non-fixing modes lint the file exactly once. In a fixing mode we repeatedly
+/// lint-and-fix, re-parsing the rewritten source after each pass with the same
+/// parser used for linting: if a pass makes the file unparseable where it
+/// previously parsed, its edits are discarded, the last good version is
+/// restored, and the discard is logged. The log names the offending check code
+/// only when the pass had exactly one fixable message; otherwise it names no
+/// code. The loop stops once a pass applies no fix or after
+/// [`MAX_FIX_ITERATIONS`].
+fn lint_path_to_fixpoint(
+    path: &Path,
+    settings: &Settings,
+    cache: &crate::cache::Mode,
+    autofix: &fixer::Mode,
+) -> Result<(Diagnostics, FixpointSummary)> {
+    // Only `Apply` rewrites the file, so every other mode is a single pass.
+    if !matches!(autofix, fixer::Mode::Apply) {
+        let diagnostics = lint_path(path, settings, cache, autofix)?;
+        return Ok((diagnostics, FixpointSummary { passes: 1, converged: true }));
+    }
+
+    let mut last_good = std::fs::read_to_string(path)?;
+    let mut passes = 0;
+    loop {
+        passes += 1;
+        let diagnostics = lint_path(path, settings, cache, autofix)?;
+        let rewritten = std::fs::read_to_string(path)?;
+
+        // No fix was applied this pass: we have reached a fixpoint.
+        if rewritten == last_good {
+            return Ok((diagnostics, FixpointSummary { passes, converged: true }));
+        }
+
+        // Re-parse the rewrite; a broken fix is discarded rather than emitted.
+        if rustpython_parser::parser::parse_program(&rewritten, &path.to_string_lossy()).is_err() {
+            std::fs::write(path, &last_good)?;
+            // Only blame a specific code when exactly one fixable message was
+            // in play: with several non-overlapping fixes applied in the same
+            // pass, picking whichever sorts first would as likely flag an
+            // innocent rule as the real offender.
+            let fixable: Vec<_> = diagnostics.messages.iter().filter(|m| m.fix.is_some()).collect();
+            let culprit = match fixable.as_slice() {
+                [message] => message.kind.code().as_ref().to_string(),
+                _ => "?".to_string(),
+            };
+            error!(
+                "Discarding autofix pass for {}: fix for {culprit} produced unparseable code",
+                path.to_string_lossy()
+            );
+            return Ok((diagnostics, FixpointSummary { passes, converged: false }));
+        }
+
+        last_good = rewritten;
+        if passes >= MAX_FIX_ITERATIONS {
+            // `diagnostics` above describes the file *before* this pass's
+            // fixes were applied, but `last_good` was just advanced to the
+            // post-fix content left on disk. Re-lint that final content
+            // (without applying further fixes) so what's reported matches
+            // what was actually left behind instead of violations that were
+            // already auto-fixed.
+            let diagnostics = lint_path(path, settings, cache, &fixer::Mode::Generate)?;
+            return Ok((diagnostics, FixpointSummary { passes, converged: false }));
+        }
+    }
+}
+
+/// Clear the terminal, mirroring the editor-adjacent watch loops in other
+/// linters so each re-lint starts from a clean screen.
+fn clear_terminal() {
+    // ANSI: clear the whole screen, then move the cursor home.
+    print!("\x1b[2J\x1b[1;1H");
+    let _ = io::stdout().flush();
+}
+
+/// Re-resolve settings for `path`, falling back to `defaults`, then lint it to
+/// a fixpoint.
+///
+/// Routes through [`lint_path_to_fixpoint`], the same helper `run` uses, so a
+/// watch-mode autofix gets the same guarantees: a fix that unlocks a further
+/// fixable violation is not left on the table on the next re-lint, and a pass
+/// that produces unparseable code is discarded and rolled back rather than
+/// written to disk. Returns `None` (after logging the error) when the file
+/// could not be checked.
+fn lint_watched_path<'a>(
+    path: &Path,
+    defaults: &'a Settings,
+    resolver: &'a crate::fs::Resolver,
+    autofix: &fixer::Mode,
+) -> Option<Diagnostics> {
+    let settings = resolver.resolve(path).unwrap_or(defaults);
+    match lint_path_to_fixpoint(path, settings, &false.into(), autofix) {
+        Ok((diagnostics, summary)) => {
+            if !summary.converged {
+                error!(
+                    "Autofix did not converge for {} after {} pass(es)",
+                    path.to_string_lossy(),
+                    summary.passes
+                );
+            }
+            Some(diagnostics)
+        }
+        Err(e) => {
+            error!("Failed to check {}: {e}", path.to_string_lossy());
+            None
+        }
+    }
+}
+
+/// Collect the set of directories to watch for a run.
+///
+/// `collect_python_files` yields the individual files to lint, which may be
+/// scattered across the tree (and the CLI roots may be single files rather than
+/// directories). Watching each file's parent directory recursively observes the
+/// same subtree the walk covered while also surfacing creates and deletes
+/// beneath it, which a per-file watch would miss.
+fn watch_dirs(paths: &[Result<ignore::DirEntry, ignore::Error>]) -> HashSet<PathBuf> {
+    paths
+        .iter()
+        .flatten()
+        .filter_map(|entry| entry.path().parent().map(Path::to_owned))
+        .collect()
+}
+
+/// Run the linter in watch mode, incrementally re-linting files as they change.
+///
+/// The initial pass lints every file and caches its `Diagnostics`. Afterwards a
+/// filesystem notifier observes the directories produced by
+/// `collect_python_files`; when events arrive they are debounced into a single
+/// batch, the affected paths are re-linted (a changed `pyproject.toml`
+/// re-resolves and invalidates its subtree), and the cached results for every
+/// untouched file are reused. The refreshed `Diagnostics` are rendered through
+/// the reporter for `format` after the screen is cleared, and the loop then
+/// blocks until the next change. `Ctrl-C` sets the shutdown flag, drops the
+/// watcher, and returns cleanly.
+pub fn run_watch(
+    files: &[PathBuf],
+    defaults: &Settings,
+    overrides: &Overrides,
+    autofix: &fixer::Mode,
+    format: SerializationFormat,
+) -> Result<()> {
+    // Install a `Ctrl-C` handler so the loop exits cleanly instead of being
+    // killed mid-render by the default signal handler. The flag is flipped from
+    // the handler and observed between debounce windows below.
+    let running = Arc::new(AtomicBool::new(true));
+    ctrlc::set_handler({
+        let running = running.clone();
+        move || running.store(false, Ordering::SeqCst)
+    })?;
+
+    // Seed the cache with a full pass over every file.
+    let (paths, mut resolver) = collect_python_files(files, overrides, defaults);
+    let mut cache: HashMap<PathBuf, Diagnostics> = HashMap::default();
+    for entry in paths.iter().flatten() {
+        let path = entry.path();
+        if let Some(diagnostics) = lint_watched_path(path, defaults, &resolver, autofix) {
+            cache.insert(path.to_owned(), diagnostics);
+        }
+    }
+    render_watch(&cache, format);
+
+    // Watch the directories `collect_python_files` actually walked, recursively,
+    // so creates and deletes beneath them surface as events. `watched` tracks
+    // what's registered so far, since a `pyproject.toml` change below may
+    // re-run `collect_python_files` and surface directories (newly included
+    // subtrees, or ones with no Python files yet at startup) that still need
+    // registering.
+    let (tx, rx) = channel();
+    let mut watcher: RecommendedWatcher = Watcher::new_immediate(move |res| {
+        if let Ok(event) = res {
+            let _ = tx.send(event);
+        }
+    })?;
+    let mut watched: HashSet<PathBuf> = HashSet::default();
+    for dir in watch_dirs(&paths) {
+        watcher.watch(&dir, RecursiveMode::Recursive)?;
+        watched.insert(dir);
+    }
+
+    while running.load(Ordering::SeqCst) {
+        // Poll for the first event so a `Ctrl-C` between bursts is noticed
+        // promptly, then drain the burst after a short quiet period so
+        // rename/create/delete triples coalesce into one pass.
+        let event = match rx.recv_timeout(WATCH_DEBOUNCE) {
+            Ok(event) => event,
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => continue,
+            // The sender is gone (the watcher was dropped): exit.
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+        let mut changed: HashSet<PathBuf> = event.paths.into_iter().collect();
+        while let Ok(event) = rx.recv_timeout(WATCH_DEBOUNCE) {
+            changed.extend(event.paths);
+        }
+
+        // A modified `pyproject.toml` invalidates the settings for its subtree,
+        // so re-resolve from scratch before re-linting.
+        if changed
+            .iter()
+            .any(|path| path.file_name().map_or(false, |name| name == "pyproject.toml"))
+        {
+            let (paths, fresh) = collect_python_files(files, overrides, defaults);
+            resolver = fresh;
+            changed.extend(paths.iter().flatten().map(|entry| entry.path().to_owned()));
+
+            // The re-walk may have surfaced directories the watcher isn't
+            // observing yet; register any that aren't already watched so
+            // creates beneath them aren't silently missed.
+            for dir in watch_dirs(&paths) {
+                if watched.insert(dir.clone()) {
+                    watcher.watch(&dir, RecursiveMode::Recursive)?;
+                }
+            }
+        }
+
+        for path in changed {
+            // A removed path drops out of the cache; otherwise re-lint it and
+            // replace its cached diagnostics.
+            if !path.is_file() {
+                cache.remove(&path);
+                continue;
+            }
+            if let Some(diagnostics) = lint_watched_path(&path, defaults, &resolver, autofix) {
+                cache.insert(path, diagnostics);
+            }
+        }
+
+        render_watch(&cache, format);
+    }
+
+    // Dropping `watcher` here closes the event channel; returning unwinds the
+    // loop so the process exits without the default `Ctrl-C` abort.
+    drop(watcher);
+    Ok(())
+}
+
+/// Clear the screen and render the merged, sorted diagnostics for the watched
+/// tree through the reporter for `format`, so watch mode honours the user's
+/// chosen output format instead of always printing bare text.
+fn render_watch(cache: &HashMap<PathBuf, Diagnostics>, format: SerializationFormat) {
+    clear_terminal();
+    let mut reporter = create_reporter(format);
+    let mut summary = Summary::default();
+    // Drive the reporter in a stable path order so streaming formats emit the
+    // same sequence each render.
+    for (path, diagnostics) in cache.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+        summary.record(diagnostics);
+        reporter.report_file(path, diagnostics);
+    }
+    reporter.finish(&summary);
 }
 
 /// Read a `String` from `stdin`.
@@ -218,6 +631,12 @@ pub fn explain(code: &CheckCode, format: SerializationFormat) -> Result<()> {
         SerializationFormat::Github => {
             bail!("`--explain` does not support GitHub format")
         }
+        SerializationFormat::Suggestions => {
+            bail!("`--explain` does not support suggestions format")
+        }
+        SerializationFormat::Sarif => {
+            bail!("`--explain` does not support SARIF format")
+        }
     };
     Ok(())
 }
@@ -0,0 +1,465 @@
+//! Streaming reporters for lint results.
+//!
+//! Rather than reducing every file's [`Diagnostics`] into one vector, sorting
+//! it, and serializing afterwards, `run` drives a [`Reporter`] as each file
+//! completes. The text reporters stream per-file output the moment a result
+//! arrives (synchronized through a channel), while the structured reporters
+//! (JSON, JUnit) buffer and emit once on [`Reporter::finish`]. This mirrors
+//! Deno's `create_reporter`/`LintReporter` split and moves summary and error
+//! counting out of the diagnostics vector.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::checks::CheckKind;
+use crate::linter::Diagnostics;
+use crate::message::Message;
+use crate::settings::types::SerializationFormat;
+
+/// Running totals reported once the run finishes.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Summary {
+    /// Number of files reported.
+    pub files: usize,
+    /// Total number of messages across all files.
+    pub messages: usize,
+    /// Number of files that failed to check.
+    pub errors: usize,
+}
+
+impl Summary {
+    /// Fold a file's diagnostics into the running totals.
+    pub fn record(&mut self, diagnostics: &Diagnostics) {
+        self.files += 1;
+        self.messages += diagnostics.messages.len();
+    }
+}
+
+/// A sink for lint results, driven once per file as the run proceeds.
+pub trait Reporter {
+    /// Report the diagnostics produced for a single file.
+    fn report_file(&mut self, path: &Path, diagnostics: &Diagnostics);
+
+    /// Report that a file could not be checked.
+    fn report_error(&mut self, path: Option<&Path>, err: &str);
+
+    /// Flush any buffered output and emit the final summary.
+    fn finish(&mut self, summary: &Summary);
+}
+
+/// Construct the reporter for a given [`SerializationFormat`].
+pub fn create_reporter(format: SerializationFormat) -> Box<dyn Reporter + Send> {
+    match format {
+        SerializationFormat::Text => Box::<TextReporter>::default(),
+        SerializationFormat::Grouped => Box::<GroupedReporter>::default(),
+        SerializationFormat::Json => Box::<JsonReporter>::default(),
+        SerializationFormat::Junit => Box::<JunitReporter>::default(),
+        SerializationFormat::Github => Box::<GithubReporter>::default(),
+        SerializationFormat::Suggestions => Box::<SuggestionsReporter>::default(),
+        SerializationFormat::Sarif => Box::<SarifReporter>::default(),
+    }
+}
+
+/// A machine-applicable fix for a single violation, in the spirit of rustfix's
+/// suggestion JSON: enough span precision for an external tool to apply several
+/// non-overlapping edits in one pass and to detect conflicts.
+#[derive(Serialize)]
+struct Suggestion {
+    /// The file the edit applies to.
+    filename: String,
+    /// The check that produced the fix.
+    code: String,
+    /// Span of the text the replacement overwrites.
+    span: Span,
+    /// Text to substitute in place of `span`.
+    replacement: String,
+    /// How safe the fix is to apply without review.
+    applicability: &'static str,
+}
+
+/// Classify how confidently an external tool can apply a fix, in rustfix's
+/// vocabulary. This is a property of the rule itself, not of how large its
+/// edit happens to be: a single-line rename can still be semantically wrong,
+/// and a safe fix (e.g. collapsing an unused multi-line import block) can
+/// still span several lines. The per-rule signal is `CheckKind::is_safe_fix`.
+fn applicability(kind: &CheckKind) -> &'static str {
+    if kind.is_safe_fix() {
+        "MachineApplicable"
+    } else {
+        "MaybeIncorrect"
+    }
+}
+
+/// A source span expressed as inclusive-start/exclusive-end line and column,
+/// matching the `Location` pairs a `Message` already carries.
+#[derive(Serialize)]
+struct Span {
+    start_row: usize,
+    start_column: usize,
+    end_row: usize,
+    end_column: usize,
+}
+
+/// Buffers fixable violations and emits them as structured suggestions on
+/// `finish`, for external appliers (editors, pre-commit hooks, CI).
+#[derive(Default)]
+struct SuggestionsReporter {
+    suggestions: Vec<Suggestion>,
+}
+
+impl Reporter for SuggestionsReporter {
+    fn report_file(&mut self, _path: &Path, diagnostics: &Diagnostics) {
+        for message in &diagnostics.messages {
+            let Some(fix) = &message.fix else {
+                continue;
+            };
+            let span = Span {
+                start_row: fix.location.row(),
+                start_column: fix.location.column(),
+                end_row: fix.end_location.row(),
+                end_column: fix.end_location.column(),
+            };
+            self.suggestions.push(Suggestion {
+                filename: message.filename.clone(),
+                code: message.kind.code().as_ref().to_string(),
+                applicability: applicability(&message.kind),
+                span,
+                replacement: fix.content.clone(),
+            });
+        }
+    }
+
+    fn report_error(&mut self, _path: Option<&Path>, err: &str) {
+        log::error!("{err}");
+    }
+
+    fn finish(&mut self, _summary: &Summary) {
+        // Buffer order is parallel-completion order; sort first so the
+        // emitted JSON is stable across runs over unchanged files, matching
+        // the other buffered reporters below.
+        self.suggestions.sort_unstable_by_key(|s| {
+            (s.filename.clone(), s.span.start_row, s.span.start_column, s.code.clone())
+        });
+        match serde_json::to_string_pretty(&self.suggestions) {
+            Ok(json) => println!("{json}"),
+            Err(e) => log::error!("Failed to serialize suggestions: {e}"),
+        }
+    }
+}
+
+/// Buffers messages and emits a SARIF 2.1.0 log on `finish`, the interchange
+/// format GitHub's code-scanning ingests. The `tool.driver` describes ruff and
+/// the rules that fired; `results[]` carry the per-message findings.
+#[derive(Default)]
+struct SarifReporter {
+    messages: Vec<Message>,
+}
+
+impl Reporter for SarifReporter {
+    fn report_file(&mut self, _path: &Path, diagnostics: &Diagnostics) {
+        self.messages.extend(diagnostics.messages.iter().cloned());
+    }
+
+    fn report_error(&mut self, _path: Option<&Path>, err: &str) {
+        log::error!("{err}");
+    }
+
+    fn finish(&mut self, _summary: &Summary) {
+        // Buffer order is parallel-completion order; sort first so `results[]`
+        // is stable across runs over unchanged files, matching the other
+        // buffered reporters below.
+        self.messages.sort_unstable();
+
+        // Collect the metadata for every rule that fired, deduplicated and
+        // ordered, pulling the same fields as the `explain` command.
+        let mut rules: BTreeMap<String, SarifRule> = BTreeMap::new();
+        let results = self
+            .messages
+            .iter()
+            .map(|message| {
+                let code = message.kind.code();
+                rules.entry(code.as_ref().to_string()).or_insert_with(|| SarifRule {
+                    id: code.as_ref().to_string(),
+                    short_description: SarifText {
+                        text: code.kind().summary(),
+                    },
+                    properties: SarifRuleProperties {
+                        category: code.category().title().to_string(),
+                    },
+                });
+                SarifResult {
+                    rule_id: code.as_ref().to_string(),
+                    level: sarif_level(code.as_ref()),
+                    message: SarifText {
+                        text: message.kind.body(),
+                    },
+                    locations: vec![SarifLocation {
+                        physical_location: SarifPhysicalLocation {
+                            artifact_location: SarifArtifactLocation {
+                                uri: message.filename.clone(),
+                            },
+                            // SARIF regions are 1-based in both axes. `row()` is
+                            // already 1-based, but ruff's `column()` is 0-based,
+                            // so shift the columns into SARIF's convention.
+                            region: SarifRegion {
+                                start_line: message.location.row(),
+                                start_column: message.location.column() + 1,
+                                end_line: message.end_location.row(),
+                                end_column: message.end_location.column() + 1,
+                            },
+                        },
+                    }],
+                }
+            })
+            .collect();
+
+        let log = SarifLog {
+            schema: "https://raw.githubusercontent.com/oasis-tcs/sarif-spec/master/Schemata/sarif-schema-2.1.0.json",
+            version: "2.1.0",
+            runs: vec![SarifRun {
+                tool: SarifTool {
+                    driver: SarifDriver {
+                        name: "ruff",
+                        information_uri: "https://github.com/charliermarsh/ruff",
+                        rules: rules.into_values().collect(),
+                    },
+                },
+                results,
+            }],
+        };
+
+        match serde_json::to_string_pretty(&log) {
+            Ok(json) => println!("{json}"),
+            Err(e) => log::error!("Failed to serialize SARIF log: {e}"),
+        }
+    }
+}
+
+/// Map a check code to its SARIF severity level.
+///
+/// pycodestyle's `E9xx` band — and ruff's own `E902` I/O error — are the
+/// failures that block analysis rather than style findings, so they surface as
+/// `error`; everything else is a `warning`. These are the two levels GitHub's
+/// code-scanning renders distinctly.
+fn sarif_level(code: &str) -> &'static str {
+    if code.starts_with("E9") {
+        "error"
+    } else {
+        "warning"
+    }
+}
+
+#[derive(Serialize)]
+struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<SarifRun>,
+}
+
+#[derive(Serialize)]
+struct SarifRun {
+    tool: SarifTool,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct SarifTool {
+    driver: SarifDriver,
+}
+
+#[derive(Serialize)]
+struct SarifDriver {
+    name: &'static str,
+    #[serde(rename = "informationUri")]
+    information_uri: &'static str,
+    rules: Vec<SarifRule>,
+}
+
+#[derive(Serialize)]
+struct SarifRule {
+    id: String,
+    #[serde(rename = "shortDescription")]
+    short_description: SarifText,
+    properties: SarifRuleProperties,
+}
+
+#[derive(Serialize)]
+struct SarifRuleProperties {
+    category: String,
+}
+
+#[derive(Serialize)]
+struct SarifResult {
+    #[serde(rename = "ruleId")]
+    rule_id: String,
+    level: &'static str,
+    message: SarifText,
+    locations: Vec<SarifLocation>,
+}
+
+#[derive(Serialize)]
+struct SarifLocation {
+    #[serde(rename = "physicalLocation")]
+    physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Serialize)]
+struct SarifPhysicalLocation {
+    #[serde(rename = "artifactLocation")]
+    artifact_location: SarifArtifactLocation,
+    region: SarifRegion,
+}
+
+#[derive(Serialize)]
+struct SarifArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize)]
+struct SarifRegion {
+    #[serde(rename = "startLine")]
+    start_line: usize,
+    #[serde(rename = "startColumn")]
+    start_column: usize,
+    #[serde(rename = "endLine")]
+    end_line: usize,
+    #[serde(rename = "endColumn")]
+    end_column: usize,
+}
+
+#[derive(Serialize)]
+struct SarifText {
+    text: String,
+}
+
+/// Return a file's messages in stable sorted order.
+///
+/// The streaming reporters emit per file rather than collecting into one vector
+/// that the old path sorted with `sort_unstable`, so each file's messages are
+/// sorted here before they are written to keep output ordered within a file.
+fn sorted_messages(diagnostics: &Diagnostics) -> Vec<Message> {
+    let mut messages = diagnostics.messages.clone();
+    messages.sort_unstable();
+    messages
+}
+
+/// Streams one line per message as each file completes.
+#[derive(Default)]
+struct TextReporter;
+
+impl Reporter for TextReporter {
+    fn report_file(&mut self, _path: &Path, diagnostics: &Diagnostics) {
+        for message in sorted_messages(diagnostics) {
+            println!("{message}");
+        }
+    }
+
+    fn report_error(&mut self, path: Option<&Path>, err: &str) {
+        match path {
+            Some(path) => eprintln!("{}: {err}", path.to_string_lossy()),
+            None => eprintln!("{err}"),
+        }
+    }
+
+    fn finish(&mut self, summary: &Summary) {
+        println!("Found {} error(s) in {} file(s).", summary.messages, summary.files);
+    }
+}
+
+/// Streams the messages for a file under a per-file header.
+#[derive(Default)]
+struct GroupedReporter;
+
+impl Reporter for GroupedReporter {
+    fn report_file(&mut self, path: &Path, diagnostics: &Diagnostics) {
+        if diagnostics.messages.is_empty() {
+            return;
+        }
+        println!("{}:", path.to_string_lossy());
+        for message in sorted_messages(diagnostics) {
+            println!("  {message}");
+        }
+    }
+
+    fn report_error(&mut self, path: Option<&Path>, err: &str) {
+        match path {
+            Some(path) => eprintln!("{}: {err}", path.to_string_lossy()),
+            None => eprintln!("{err}"),
+        }
+    }
+
+    fn finish(&mut self, summary: &Summary) {
+        println!("Found {} error(s) in {} file(s).", summary.messages, summary.files);
+    }
+}
+
+/// Buffers every message and emits a single JSON array on `finish`.
+#[derive(Default)]
+struct JsonReporter {
+    messages: Vec<Message>,
+}
+
+impl Reporter for JsonReporter {
+    fn report_file(&mut self, _path: &Path, diagnostics: &Diagnostics) {
+        self.messages.extend(diagnostics.messages.iter().cloned());
+    }
+
+    fn report_error(&mut self, _path: Option<&Path>, err: &str) {
+        log::error!("{err}");
+    }
+
+    fn finish(&mut self, _summary: &Summary) {
+        self.messages.sort_unstable();
+        match serde_json::to_string_pretty(&self.messages) {
+            Ok(json) => println!("{json}"),
+            Err(e) => log::error!("Failed to serialize diagnostics: {e}"),
+        }
+    }
+}
+
+/// Buffers every message and emits a JUnit report on `finish`.
+#[derive(Default)]
+struct JunitReporter {
+    messages: Vec<Message>,
+}
+
+impl Reporter for JunitReporter {
+    fn report_file(&mut self, _path: &Path, diagnostics: &Diagnostics) {
+        self.messages.extend(diagnostics.messages.iter().cloned());
+    }
+
+    fn report_error(&mut self, _path: Option<&Path>, err: &str) {
+        log::error!("{err}");
+    }
+
+    fn finish(&mut self, _summary: &Summary) {
+        self.messages.sort_unstable();
+        let mut diagnostics = Diagnostics::default();
+        diagnostics.messages.clone_from(&self.messages);
+        print!("{}", crate::message::render_junit(&diagnostics));
+    }
+}
+
+/// Streams GitHub Actions annotations per file.
+#[derive(Default)]
+struct GithubReporter;
+
+impl Reporter for GithubReporter {
+    fn report_file(&mut self, _path: &Path, diagnostics: &Diagnostics) {
+        for message in sorted_messages(diagnostics) {
+            print!("{}", crate::message::render_github(&message));
+        }
+    }
+
+    fn report_error(&mut self, path: Option<&Path>, err: &str) {
+        match path {
+            Some(path) => eprintln!("{}: {err}", path.to_string_lossy()),
+            None => eprintln!("{err}"),
+        }
+    }
+
+    fn finish(&mut self, _summary: &Summary) {}
+}